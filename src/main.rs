@@ -1,134 +1,450 @@
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
-use tracing::{info, warn, error};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info, warn};
+
+/// Capacity of the per-market level-update broadcast channel. Sized generously
+/// so a slow subscriber can fall behind a burst of updates without the engine
+/// blocking; a lagging subscriber just gets `RecvError::Lagged` and should
+/// resubscribe to get a fresh checkpoint.
+const LEVEL_UPDATE_CHANNEL_CAPACITY: usize = 4096;
+
+/// Reserved high bit of the order-id space used to tag synthetic per-level
+/// orders created by [`Orderbook::sync_level`], keeping them out of the way
+/// of real exchange-assigned order ids. The next-highest bit tags the side,
+/// since bid/ask synthetic ids are otherwise derived from the same price lots.
+const SYNTHETIC_ORDER_ID_FLAG: u64 = 1 << 63;
+const SYNTHETIC_ORDER_ID_BID_FLAG: u64 = 1 << 62;
+
+fn synthetic_order_id(is_buy: bool, price_lots: i64) -> u64 {
+    let side_flag = if is_buy { SYNTHETIC_ORDER_ID_BID_FLAG } else { 0 };
+    SYNTHETIC_ORDER_ID_FLAG | side_flag | (price_lots as u64 & !(SYNTHETIC_ORDER_ID_FLAG | SYNTHETIC_ORDER_ID_BID_FLAG))
+}
+
+/// Per-market tick/lot sizing used to convert between human-readable f64
+/// prices/sizes and the integer `price_lots`/`size_lots` the book actually
+/// trades in. Keeping the book's internal state integral avoids the
+/// floating-point accumulation error (or outright corruption, as with the
+/// old `to_bits`-as-an-integer trick) that comes from repeatedly adding and
+/// subtracting raw f64 sizes.
+#[derive(Debug, Clone, Copy)]
+pub struct MarketConfig {
+    pub tick_size: f64,
+    pub lot_size: f64,
+}
+
+impl MarketConfig {
+    pub fn price_to_lots(&self, price: f64) -> i64 {
+        (price / self.tick_size).round() as i64
+    }
+
+    pub fn size_to_lots(&self, size: f64) -> i64 {
+        (size / self.lot_size).round() as i64
+    }
+
+    pub fn price_lots_to_ui(&self, price_lots: i64) -> f64 {
+        price_lots as f64 * self.tick_size
+    }
+
+    pub fn base_lots_to_ui(&self, size_lots: i64) -> f64 {
+        size_lots as f64 * self.lot_size
+    }
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self { tick_size: 0.01, lot_size: 0.001 }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub order_id: u64,
-    pub price: f64,
-    pub size: f64,
+    pub price_lots: i64,
+    pub size_lots: i64,
     pub timestamp: i64,
 }
 
 #[derive(Debug, Default)]
 pub struct PriceLevel {
-    pub price: f64,
+    pub price_lots: i64,
     pub orders: DashMap<u64, Order>,
     pub total_size: AtomicU64,
 }
 
 impl PriceLevel {
-    fn new(price: f64) -> Self {
+    fn new(price_lots: i64) -> Self {
         Self {
-            price,
+            price_lots,
             orders: DashMap::new(),
             total_size: AtomicU64::new(0),
         }
     }
 
     fn add_order(&self, order: Order) {
-        let size_bits = order.size.to_bits();
+        let size_lots = order.size_lots as u64;
         self.orders.insert(order.order_id, order);
-        self.total_size.fetch_add(size_bits, Ordering::Relaxed);
+        self.total_size.fetch_add(size_lots, Ordering::Relaxed);
     }
 
     fn remove_order(&self, order_id: u64) -> Option<Order> {
         if let Some((_, order)) = self.orders.remove(&order_id) {
-            let size_bits = order.size.to_bits();
-            self.total_size.fetch_sub(size_bits, Ordering::Relaxed);
+            self.total_size.fetch_sub(order.size_lots as u64, Ordering::Relaxed);
             Some(order)
         } else {
             None
         }
     }
 
-    fn total_size(&self) -> f64 {
-        f64::from_bits(self.total_size.load(Ordering::Relaxed))
+    /// Reduces a resting order's size by `lots` without removing it, used
+    /// for partial fills during matching.
+    fn reduce_order(&self, order_id: u64, lots: i64) {
+        if let Some(mut entry) = self.orders.get_mut(&order_id) {
+            entry.size_lots -= lots;
+        }
+        self.total_size.fetch_sub(lots as u64, Ordering::Relaxed);
+    }
+
+    fn total_size_lots(&self) -> i64 {
+        self.total_size.load(Ordering::Relaxed) as i64
+    }
+
+    /// Resting orders at this level in FIFO (timestamp, then order id to
+    /// break ties) order, for matching against an aggressive order.
+    fn fifo_orders(&self) -> Vec<Order> {
+        let mut orders: Vec<Order> = self.orders.iter().map(|entry| entry.value().clone()).collect();
+        orders.sort_by_key(|order| (order.timestamp, order.order_id));
+        orders
     }
 }
 
 pub struct Orderbook {
     market_id: u16,
-    bids: Arc<RwLock<BTreeMap<u64, Arc<PriceLevel>>>>, // Negative price as key for desc order
-    asks: Arc<RwLock<BTreeMap<u64, Arc<PriceLevel>>>>,
-    order_locations: DashMap<u64, (bool, u64)>, // order_id -> (is_bid, price_bits)
+    config: MarketConfig,
+    bids: Arc<RwLock<BTreeMap<i64, Arc<PriceLevel>>>>, // keyed i64::MAX - price_lots for desc order
+    asks: Arc<RwLock<BTreeMap<i64, Arc<PriceLevel>>>>, // keyed price_lots ascending
+    order_locations: DashMap<u64, (bool, i64)>, // order_id -> (is_bid, price_lots)
     sequence: AtomicU64,
     last_update: AtomicU64,
+    last_trade_price_bits: AtomicU64,
+    updates: broadcast::Sender<BookEvent>,
+    /// Serializes the whole would-cross-check/match/rest critical section in
+    /// [`Orderbook::add_order`] so concurrent callers can't interleave their
+    /// book mutations. Each side's `bids`/`asks` lock only protects that
+    /// side's own map; that alone leaves a window between `would_cross`'s
+    /// read and `rest_order`'s write (and between one order's match walk and
+    /// the other's rest) where a second, concurrently-placed order could
+    /// slip in and leave the book crossed and unmatched, or let a post-only
+    /// order rest at a price that's crossing by the time it lands. This
+    /// mutex closes that window.
+    matching_lock: Mutex<()>,
 }
 
 impl Orderbook {
-    pub fn new(market_id: u16) -> Self {
+    pub fn new(market_id: u16, config: MarketConfig) -> Self {
+        let (updates, _) = broadcast::channel(LEVEL_UPDATE_CHANNEL_CAPACITY);
         Self {
             market_id,
+            config,
             bids: Arc::new(RwLock::new(BTreeMap::new())),
             asks: Arc::new(RwLock::new(BTreeMap::new())),
             order_locations: DashMap::new(),
             sequence: AtomicU64::new(0),
             last_update: AtomicU64::new(0),
+            last_trade_price_bits: AtomicU64::new(0),
+            updates,
+            matching_lock: Mutex::new(()),
+        }
+    }
+
+    fn bid_key(price_lots: i64) -> i64 {
+        i64::MAX - price_lots // descending order when iterating a BTreeMap ascending
+    }
+
+    /// Places an order, matching it against the opposite side of the book
+    /// first and resting whatever remains (per `order_type`'s rules), and
+    /// returns the fills produced. See [`OrderType`] for how each variant
+    /// handles a crossing price and a partially-filled remainder. `price`
+    /// and `size` are human-readable (UI) units; they're converted to
+    /// integer lots via `self.config` before touching the book.
+    pub fn add_order(&self, order_id: u64, is_buy: bool, price: f64, size: f64, timestamp: i64, order_type: OrderType) -> Vec<Fill> {
+        // Held for the whole would-cross-check/match/rest critical section;
+        // see `matching_lock`'s doc comment for why the per-side book locks
+        // alone aren't enough.
+        let _matching_guard = self.matching_lock.lock();
+
+        let mut fills = Vec::new();
+        let price_lots = self.config.price_to_lots(price);
+        let size_lots = self.config.size_to_lots(size);
+
+        if order_type == OrderType::PostOnly {
+            if self.would_cross(is_buy, price_lots) {
+                return fills; // reject: a post-only order never rests at a crossing price
+            }
+            self.rest_order(order_id, is_buy, price_lots, size_lots, timestamp);
+            return fills;
+        }
+
+        let match_price_lots = match order_type {
+            OrderType::Market => if is_buy { i64::MAX } else { i64::MIN },
+            OrderType::Limit | OrderType::ImmediateOrCancel => price_lots,
+            OrderType::PostOnly => unreachable!("handled above"),
+        };
+
+        let remaining_lots = self.match_against_book(order_id, is_buy, match_price_lots, size_lots, timestamp, &mut fills);
+
+        let rests = remaining_lots > 0 && !matches!(order_type, OrderType::Market | OrderType::ImmediateOrCancel);
+        if rests {
+            self.rest_order(order_id, is_buy, price_lots, remaining_lots, timestamp);
         }
+
+        for fill in &fills {
+            self.publish_fill(fill.clone());
+        }
+
+        fills
     }
 
-    pub fn add_order(&self, order_id: u64, is_buy: bool, price: f64, size: f64, timestamp: i64) {
+    /// Inserts an order directly as a resting level order with no matching,
+    /// used both for the remainder of a partially-filled `add_order` and for
+    /// mirroring a remote venue's book via [`Orderbook::sync_level`].
+    fn rest_order(&self, order_id: u64, is_buy: bool, price_lots: i64, size_lots: i64, timestamp: i64) {
         let order = Order {
             order_id,
-            price,
-            size,
+            price_lots,
+            size_lots,
             timestamp,
         };
 
-        let price_bits = price.to_bits();
-
-        if is_buy {
+        let (level_size_lots, order_count) = if is_buy {
             let mut bids = self.bids.write();
             let level = bids
-                .entry(u64::MAX - price_bits) // Negative for descending order
-                .or_insert_with(|| Arc::new(PriceLevel::new(price)));
+                .entry(Self::bid_key(price_lots))
+                .or_insert_with(|| Arc::new(PriceLevel::new(price_lots)));
             level.add_order(order);
+            (level.total_size_lots(), level.orders.len())
         } else {
             let mut asks = self.asks.write();
             let level = asks
-                .entry(price_bits)
-                .or_insert_with(|| Arc::new(PriceLevel::new(price)));
+                .entry(price_lots)
+                .or_insert_with(|| Arc::new(PriceLevel::new(price_lots)));
             level.add_order(order);
+            (level.total_size_lots(), level.orders.len())
+        };
+
+        self.order_locations.insert(order_id, (is_buy, price_lots));
+        let sequence = self.next_sequence();
+        self.last_update.store(timestamp as u64, Ordering::Relaxed);
+
+        self.publish_level_update(is_buy, self.config.price_lots_to_ui(price_lots), self.config.base_lots_to_ui(level_size_lots), order_count, sequence, timestamp);
+    }
+
+    /// Whether a new order at `price_lots` would immediately cross the best
+    /// opposite-side level, used to reject post-only orders.
+    fn would_cross(&self, is_buy: bool, price_lots: i64) -> bool {
+        if is_buy {
+            self.asks.read().iter().next().is_some_and(|(_, level)| price_lots >= level.price_lots)
+        } else {
+            self.bids.read().iter().next().is_some_and(|(_, level)| price_lots <= level.price_lots)
+        }
+    }
+
+    /// Walks the opposite side of the book best-price-first, matching
+    /// resting orders in FIFO (timestamp) order within each level, until
+    /// `remaining_lots` is exhausted or the book no longer crosses
+    /// `match_price_lots`. Returns whatever size is left unfilled. Both
+    /// `bids` and `asks` are keyed so that iterating a `BTreeMap` ascending
+    /// already yields best-price-first, so the same walk works for either
+    /// side.
+    fn match_against_book(
+        &self,
+        taker_order_id: u64,
+        is_buy: bool,
+        match_price_lots: i64,
+        mut remaining_lots: i64,
+        timestamp: i64,
+        fills: &mut Vec<Fill>,
+    ) -> i64 {
+        let opposite = if is_buy { &self.asks } else { &self.bids };
+        let mut drained_levels = Vec::new();
+
+        {
+            let mut book = opposite.write();
+            for (key, level) in book.iter() {
+                if remaining_lots <= 0 {
+                    break;
+                }
+                let crosses = if is_buy { match_price_lots >= level.price_lots } else { match_price_lots <= level.price_lots };
+                if !crosses {
+                    break;
+                }
+
+                for maker in level.fifo_orders() {
+                    if remaining_lots <= 0 {
+                        break;
+                    }
+                    let trade_lots = remaining_lots.min(maker.size_lots);
+                    if trade_lots <= 0 {
+                        continue;
+                    }
+
+                    remaining_lots -= trade_lots;
+                    if trade_lots >= maker.size_lots {
+                        level.remove_order(maker.order_id);
+                        self.order_locations.remove(&maker.order_id);
+                    } else {
+                        level.reduce_order(maker.order_id, trade_lots);
+                    }
+
+                    let trade_price = self.config.price_lots_to_ui(level.price_lots);
+                    self.last_trade_price_bits.store(trade_price.to_bits(), Ordering::Relaxed);
+                    fills.push(Fill {
+                        market_id: self.market_id,
+                        maker_order_id: maker.order_id,
+                        taker_order_id,
+                        price: trade_price,
+                        size: self.config.base_lots_to_ui(trade_lots),
+                        timestamp,
+                    });
+                }
+
+                let sequence = self.next_sequence();
+                let level_price = self.config.price_lots_to_ui(level.price_lots);
+                if level.orders.is_empty() {
+                    drained_levels.push(*key);
+                    self.publish_level_update(!is_buy, level_price, 0.0, 0, sequence, timestamp);
+                } else {
+                    self.publish_level_update(!is_buy, level_price, self.config.base_lots_to_ui(level.total_size_lots()), level.orders.len(), sequence, timestamp);
+                }
+            }
+
+            for key in drained_levels {
+                book.remove(&key);
+            }
         }
 
-        self.order_locations.insert(order_id, (is_buy, price_bits));
-        self.sequence.fetch_add(1, Ordering::Relaxed);
         self.last_update.store(timestamp as u64, Ordering::Relaxed);
+        remaining_lots
+    }
+
+    /// Monotonically increasing sequence number for this book, bumped once
+    /// per emitted [`LevelUpdate`].
+    fn next_sequence(&self) -> u64 {
+        self.sequence.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn publish_fill(&self, fill: Fill) {
+        let _ = self.updates.send(BookEvent::Fill(fill));
     }
 
     pub fn cancel_order(&self, order_id: u64) -> bool {
-        if let Some((_, (is_buy, price_bits))) = self.order_locations.remove(&order_id) {
-            if is_buy {
+        if let Some((_, (is_buy, price_lots))) = self.order_locations.remove(&order_id) {
+            let (level_size_lots, order_count, timestamp) = if is_buy {
                 let mut bids = self.bids.write();
-                let key = u64::MAX - price_bits;
-                if let Some(level) = bids.get(&key) {
-                    level.remove_order(order_id);
-                    if level.orders.is_empty() {
+                let key = Self::bid_key(price_lots);
+                let removed = bids.get(&key).and_then(|level| level.remove_order(order_id));
+                let (level_size_lots, order_count) = match bids.get(&key) {
+                    Some(level) if !level.orders.is_empty() => (level.total_size_lots(), level.orders.len()),
+                    _ => {
                         bids.remove(&key);
+                        (0, 0)
                     }
-                }
+                };
+                (level_size_lots, order_count, removed.map(|o| o.timestamp).unwrap_or(0))
             } else {
                 let mut asks = self.asks.write();
-                if let Some(level) = asks.get(&price_bits) {
-                    level.remove_order(order_id);
-                    if level.orders.is_empty() {
-                        asks.remove(&price_bits);
+                let removed = asks.get(&price_lots).and_then(|level| level.remove_order(order_id));
+                let (level_size_lots, order_count) = match asks.get(&price_lots) {
+                    Some(level) if !level.orders.is_empty() => (level.total_size_lots(), level.orders.len()),
+                    _ => {
+                        asks.remove(&price_lots);
+                        (0, 0)
                     }
-                }
-            }
-            self.sequence.fetch_add(1, Ordering::Relaxed);
+                };
+                (level_size_lots, order_count, removed.map(|o| o.timestamp).unwrap_or(0))
+            };
+
+            let sequence = self.next_sequence();
+            self.last_update.store(timestamp as u64, Ordering::Relaxed);
+            self.publish_level_update(is_buy, self.config.price_lots_to_ui(price_lots), self.config.base_lots_to_ui(level_size_lots), order_count, sequence, timestamp);
             true
         } else {
             false
         }
     }
 
+    /// Broadcasts a `LevelUpdate` for the given side/price to all current subscribers.
+    /// `level_size` of `0.0` signals that the level has been fully removed. Errors
+    /// (no active subscribers) are intentionally ignored.
+    fn publish_level_update(
+        &self,
+        is_bid: bool,
+        price: f64,
+        level_size: f64,
+        order_count: usize,
+        sequence: u64,
+        timestamp: i64,
+    ) {
+        let _ = self.updates.send(BookEvent::Level(LevelUpdate {
+            market_id: self.market_id,
+            is_bid,
+            price,
+            size: level_size,
+            order_count,
+            sequence,
+            timestamp,
+        }));
+    }
+
+    /// Replaces the entire aggregate size at `price` with `size` (as opposed to
+    /// `add_order`, which adds to it), matching the semantics of an exchange
+    /// depth-diff event where each entry is the level's new absolute size and
+    /// `0.0` means the level is removed. Used to drive a book from a
+    /// [`DepthCacheSync`] rather than from locally generated orders. The
+    /// replaced level is represented internally as a single synthetic order,
+    /// so `LevelUpdate::order_count` reports `1` (or `0` when removed) rather
+    /// than a real resting-order count.
+    pub fn sync_level(&self, is_buy: bool, price: f64, size: f64, timestamp: i64) {
+        let price_lots = self.config.price_to_lots(price);
+        let size_lots = self.config.size_to_lots(size);
+        let order_id = synthetic_order_id(is_buy, price_lots);
+        self.cancel_order(order_id);
+        if size_lots > 0 {
+            self.rest_order(order_id, is_buy, price_lots, size_lots, timestamp);
+        }
+    }
+
+    /// Cancels every resting order, used to reset a book to empty before
+    /// loading a fresh REST snapshot in [`DepthCacheSync::apply_snapshot`].
+    pub fn clear(&self) {
+        let order_ids: Vec<u64> = self.order_locations.iter().map(|entry| *entry.key()).collect();
+        for order_id in order_ids {
+            self.cancel_order(order_id);
+        }
+    }
+
+    /// Subscribes to the incremental level-update feed for this book. Returns a
+    /// checkpoint of the book's current state alongside the receiver so a caller
+    /// can seed its local copy before applying subsequent deltas. Events with a
+    /// `sequence` at or before the checkpoint's `sequence` should be discarded by
+    /// the caller as already reflected in the checkpoint.
+    pub fn subscribe(&self) -> (BookCheckpoint, broadcast::Receiver<BookEvent>) {
+        let rx = self.updates.subscribe();
+        let snapshot = self.get_snapshot(usize::MAX);
+        let checkpoint = BookCheckpoint {
+            market_id: snapshot.market_id,
+            sequence: snapshot.sequence,
+            timestamp: snapshot.timestamp,
+            bids: snapshot.bids,
+            asks: snapshot.asks,
+        };
+        (checkpoint, rx)
+    }
+
     pub fn get_snapshot(&self, depth: usize) -> OrderbookSnapshot {
         let bids = self.bids.read();
         let asks = self.asks.read();
@@ -137,8 +453,8 @@ impl Orderbook {
             .iter()
             .take(depth)
             .map(|(_, level)| Level {
-                price: level.price,
-                size: level.total_size(),
+                price: self.config.price_lots_to_ui(level.price_lots),
+                size: self.config.base_lots_to_ui(level.total_size_lots()),
                 orders: level.orders.len(),
             })
             .collect();
@@ -147,8 +463,8 @@ impl Orderbook {
             .iter()
             .take(depth)
             .map(|(_, level)| Level {
-                price: level.price,
-                size: level.total_size(),
+                price: self.config.price_lots_to_ui(level.price_lots),
+                size: self.config.base_lots_to_ui(level.total_size_lots()),
                 orders: level.orders.len(),
             })
             .collect();
@@ -168,9 +484,85 @@ impl Orderbook {
             spread,
         }
     }
+
+    /// Top-of-book bid as `(price, size)`, read from the first entry of the
+    /// best-price-first sorted map: O(log n) in the number of levels, with
+    /// no scan of the levels behind it.
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids.read().iter().next().map(|(_, level)| {
+            (self.config.price_lots_to_ui(level.price_lots), self.config.base_lots_to_ui(level.total_size_lots()))
+        })
+    }
+
+    /// Top-of-book ask as `(price, size)`, read from the first entry of the
+    /// best-price-first sorted map: O(log n) in the number of levels, with
+    /// no scan of the levels behind it.
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks.read().iter().next().map(|(_, level)| {
+            (self.config.price_lots_to_ui(level.price_lots), self.config.base_lots_to_ui(level.total_size_lots()))
+        })
+    }
+
+    /// Midpoint of the best bid and ask, or `None` if either side is empty.
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => Some((bid + ask) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Best ask minus best bid, or `None` if either side is empty.
+    pub fn spread(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => Some(ask - bid),
+            _ => None,
+        }
+    }
+
+    /// Price of the most recent fill on this book, if any have occurred.
+    pub fn last_trade_price(&self) -> Option<f64> {
+        let bits = self.last_trade_price_bits.load(Ordering::Relaxed);
+        if bits == 0 {
+            None
+        } else {
+            Some(f64::from_bits(bits))
+        }
+    }
+
+    /// Depth aggregated into the two-array `{bids: [[price, size]...], asks:
+    /// [...]}` shape used by CoinGecko-style `/orderbook` market-data routes.
+    pub fn get_orderbook_with_depth(&self, depth: usize) -> CoinGeckoOrderbook {
+        let bids = self.bids.read()
+            .iter()
+            .take(depth)
+            .map(|(_, level)| [self.config.price_lots_to_ui(level.price_lots), self.config.base_lots_to_ui(level.total_size_lots())])
+            .collect();
+
+        let asks = self.asks.read()
+            .iter()
+            .take(depth)
+            .map(|(_, level)| [self.config.price_lots_to_ui(level.price_lots), self.config.base_lots_to_ui(level.total_size_lots())])
+            .collect();
+
+        CoinGeckoOrderbook { bids, asks }
+    }
+
+    /// A stable market-data summary: best bid/ask, last trade price, and the
+    /// book's current sequence/timestamp, without parsing the full debug
+    /// [`OrderbookSnapshot`].
+    pub fn ticker(&self) -> Ticker {
+        Ticker {
+            market_id: self.market_id,
+            best_bid: self.best_bid(),
+            best_ask: self.best_ask(),
+            last_price: self.last_trade_price(),
+            sequence: self.sequence.load(Ordering::Relaxed),
+            timestamp: self.last_update.load(Ordering::Relaxed) as i64,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Level {
     pub price: f64,
     pub size: f64,
@@ -187,6 +579,91 @@ pub struct OrderbookSnapshot {
     pub spread: f64,
 }
 
+/// Depth in the `{bids: [[price, size]...], asks: [...]}` shape CoinGecko
+/// and similar market-data consumers expect from an `/orderbook` route,
+/// rather than the debug-oriented [`OrderbookSnapshot`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CoinGeckoOrderbook {
+    pub bids: Vec<[f64; 2]>,
+    pub asks: Vec<[f64; 2]>,
+}
+
+/// A stable, lightweight market-data summary for a single market: top of
+/// book on both sides, the last traded price, and the book's sequence so
+/// consumers can tell how fresh it is.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ticker {
+    pub market_id: u16,
+    pub best_bid: Option<(f64, f64)>,
+    pub best_ask: Option<(f64, f64)>,
+    pub last_price: Option<f64>,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// An incremental change to a single price level, emitted after every
+/// order placement/cancellation/modification. `size` is the level's new
+/// aggregate size; `size == 0.0` means the level no longer exists.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelUpdate {
+    pub market_id: u16,
+    pub is_bid: bool,
+    pub price: f64,
+    pub size: f64,
+    pub order_count: usize,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// A full-depth snapshot paired with the sequence it was taken at, sent to a
+/// subscriber immediately on `subscribe()` so it can reconstruct book state
+/// before applying subsequent `LevelUpdate`s.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookCheckpoint {
+    pub market_id: u16,
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+/// A trade produced by `Orderbook::add_order` matching an aggressive order
+/// against resting liquidity. `maker_order_id` is the resting order that was
+/// filled; `taker_order_id` is the incoming order that crossed the book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub market_id: u16,
+    pub maker_order_id: u64,
+    pub taker_order_id: u64,
+    pub price: f64,
+    pub size: f64,
+    pub timestamp: i64,
+}
+
+/// Everything broadcast on an `Orderbook`'s update stream: both book-depth
+/// changes and the fills produced while matching.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BookEvent {
+    Level(LevelUpdate),
+    Fill(Fill),
+}
+
+/// How an incoming order should behave when it crosses the opposite side of
+/// the book, and what happens to any unfilled remainder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    /// Matches against crossing liquidity, then rests any remainder at `price`.
+    #[default]
+    Limit,
+    /// Matches against crossing liquidity at any price; any remainder is dropped.
+    Market,
+    /// Matches against crossing liquidity at `price`; any remainder is dropped.
+    ImmediateOrCancel,
+    /// Rejected outright if it would cross; otherwise rests at `price` like `Limit`.
+    PostOnly,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OrderAction {
     pub action: String,
@@ -196,61 +673,248 @@ pub struct OrderAction {
     pub price: Option<f64>,
     pub size: Option<f64>,
     pub timestamp: Option<i64>,
+    /// Limit/market/IOC/post-only behavior for `action == "place"` and
+    /// `"modify"`; defaults to `OrderType::Limit` when omitted.
+    pub order_type: Option<OrderType>,
+    /// `U` from an exchange depth-diff event (first update id covered by this
+    /// event). Only set for `action == "sync_level"` events driving a
+    /// `DepthCacheSync`; `None` for locally generated order actions.
+    pub first_update_id: Option<u64>,
+    /// `u` from an exchange depth-diff event (final update id covered by this
+    /// event). See [`OrderAction::first_update_id`].
+    pub final_update_id: Option<u64>,
+}
+
+/// Error returned by [`DepthCacheSync::ingest`] when a depth-diff event does
+/// not chain onto the last applied one. The caller should fetch a fresh REST
+/// snapshot and feed it to [`DepthCacheSync::apply_snapshot`]; until then,
+/// incoming events are buffered rather than applied.
+#[derive(Debug)]
+pub enum DepthSyncError {
+    SequenceGap { expected: u64, got: u64 },
+}
+
+/// Drives an [`Orderbook`] from an exchange's websocket depth-diff stream
+/// (Binance-style `U`/`u` update ids) instead of from locally generated
+/// orders. Diffs that arrive before the baseline REST snapshot, or after a
+/// detected sequence gap, are buffered until `apply_snapshot` re-establishes
+/// a baseline.
+pub struct DepthCacheSync {
+    book: Arc<Orderbook>,
+    last_update_id: AtomicU64,
+    synced: std::sync::atomic::AtomicBool,
+    buffer: RwLock<Vec<OrderAction>>,
+}
+
+impl DepthCacheSync {
+    pub fn new(book: Arc<Orderbook>) -> Self {
+        Self {
+            book,
+            last_update_id: AtomicU64::new(0),
+            synced: std::sync::atomic::AtomicBool::new(false),
+            buffer: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.synced.load(Ordering::Relaxed)
+    }
+
+    /// Loads a REST depth snapshot as the new baseline, discards any buffered
+    /// events already covered by it, and replays the rest as long as they
+    /// chain contiguously from `last_update_id`. Per the exchange's documented
+    /// rule, the first replayed event only needs `U <= last_update_id + 1 <=
+    /// u`; every event after that must have `U == previous_event.u + 1`. If
+    /// the buffer can't be fully drained (a gap remains), the cache stays
+    /// unsynced and waits for the next snapshot.
+    pub fn apply_snapshot(&self, last_update_id: u64, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) {
+        self.book.clear();
+        for (price, size) in bids {
+            self.book.sync_level(true, price, size, 0);
+        }
+        for (price, size) in asks {
+            self.book.sync_level(false, price, size, 0);
+        }
+        self.last_update_id.store(last_update_id, Ordering::Relaxed);
+        self.synced.store(true, Ordering::Relaxed);
+
+        let mut buffer = self.buffer.write();
+        let pending = std::mem::take(&mut *buffer);
+        let mut expected = last_update_id + 1;
+        let mut baseline_found = false;
+        let mut replayed_all = true;
+        for action in pending {
+            let (first, final_) = match (action.first_update_id, action.final_update_id) {
+                (Some(u1), Some(u2)) => (u1, u2),
+                _ => continue,
+            };
+            if final_ <= last_update_id {
+                continue; // fully covered by the snapshot already
+            }
+            if !baseline_found {
+                if first > expected {
+                    replayed_all = false;
+                    break; // gap between the snapshot and the oldest buffered event
+                }
+                baseline_found = true;
+            } else if first != expected {
+                replayed_all = false;
+                break; // gap between buffered events
+            }
+            self.apply_action_unchecked(&action);
+            expected = final_ + 1;
+            self.last_update_id.store(final_, Ordering::Relaxed);
+        }
+
+        if !replayed_all {
+            // the loop above broke out early on a gap, leaving events in
+            // `pending` unreplayed; don't claim to be synced on a book that's
+            // silently missing those updates
+            buffer.clear();
+            self.synced.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Feeds one depth-diff event (decomposed into a single price-level
+    /// `OrderAction` with `action == "sync_level"`) into the cache. Events
+    /// that arrive before the cache is synced, or once a gap has been
+    /// detected, are buffered rather than applied.
+    pub fn ingest(&self, action: OrderAction) -> Result<(), DepthSyncError> {
+        let (first, final_) = match (action.first_update_id, action.final_update_id) {
+            (Some(u1), Some(u2)) => (u1, u2),
+            _ => return Ok(()),
+        };
+
+        let last = self.last_update_id.load(Ordering::Relaxed);
+        if final_ <= last {
+            return Ok(()); // stale, already reflected in the current baseline
+        }
+
+        if !self.synced.load(Ordering::Relaxed) {
+            self.buffer.write().push(action);
+            return Ok(());
+        }
+
+        let expected = last + 1;
+        if first != expected {
+            self.synced.store(false, Ordering::Relaxed);
+            self.buffer.write().clear();
+            return Err(DepthSyncError::SequenceGap { expected, got: first });
+        }
+
+        self.apply_action_unchecked(&action);
+        self.last_update_id.store(final_, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn apply_action_unchecked(&self, action: &OrderAction) {
+        if let (Some(side), Some(price), Some(size)) = (&action.side, action.price, action.size) {
+            self.book.sync_level(side == "buy", price, size, action.timestamp.unwrap_or(0));
+        }
+    }
 }
 
 pub struct OrderbookEngine {
     orderbooks: DashMap<u16, Arc<Orderbook>>,
     order_processor: mpsc::Sender<OrderAction>,
+    sync_controllers: DashMap<u16, Arc<DepthCacheSync>>,
+    market_configs: DashMap<u16, MarketConfig>,
 }
 
 impl OrderbookEngine {
-    pub fn new(market_ids: Vec<u16>) -> (Self, mpsc::Receiver<OrderAction>) {
+    /// `markets` pairs each market id with the tick/lot sizing its book
+    /// should trade in; see [`MarketConfig`].
+    pub fn new(markets: Vec<(u16, MarketConfig)>) -> (Self, mpsc::Receiver<OrderAction>) {
         let (tx, rx) = mpsc::channel(100_000);
-        
-        let mut orderbooks = DashMap::new();
-        for market_id in market_ids {
-            orderbooks.insert(market_id, Arc::new(Orderbook::new(market_id)));
+
+        let orderbooks = DashMap::new();
+        let market_configs = DashMap::new();
+        for (market_id, config) in markets {
+            orderbooks.insert(market_id, Arc::new(Orderbook::new(market_id, config)));
+            market_configs.insert(market_id, config);
         }
 
         (
             Self {
                 orderbooks,
                 order_processor: tx,
+                sync_controllers: DashMap::new(),
+                market_configs,
             },
             rx,
         )
     }
 
+    pub fn get_market_config(&self, market_id: u16) -> Option<MarketConfig> {
+        self.market_configs.get(&market_id).map(|entry| *entry)
+    }
+
+    /// Like `new`, but also sets up a [`DepthCacheSync`] per market so the
+    /// engine can mirror a remote venue's order book from its websocket diff
+    /// feed. Fetch each market's REST snapshot and call
+    /// `get_depth_cache_sync(market_id).apply_snapshot(..)` before feeding
+    /// `"sync_level"` actions through `process_orders`.
+    pub fn new_with_depth_cache_sync(markets: Vec<(u16, MarketConfig)>) -> (Self, mpsc::Receiver<OrderAction>) {
+        let (engine, rx) = Self::new(markets);
+        for entry in engine.orderbooks.iter() {
+            let market_id = *entry.key();
+            let book = entry.value().clone();
+            engine.sync_controllers.insert(market_id, Arc::new(DepthCacheSync::new(book)));
+        }
+        (engine, rx)
+    }
+
+    pub fn get_depth_cache_sync(&self, market_id: u16) -> Option<Arc<DepthCacheSync>> {
+        self.sync_controllers.get(&market_id).map(|entry| entry.clone())
+    }
+
     pub async fn process_orders(self: Arc<Self>, mut rx: mpsc::Receiver<OrderAction>) {
         while let Some(action) = rx.recv().await {
             if let Some(book) = self.orderbooks.get(&action.asset) {
                 match action.action.as_str() {
+                    "sync_level" => {
+                        if let Some(sync) = self.sync_controllers.get(&action.asset) {
+                            if let Err(err) = sync.ingest(action) {
+                                warn!(market_id = book.market_id, ?err, "depth cache sequence gap, awaiting resync");
+                            }
+                        }
+                    }
                     "place" => {
-                        if let (Some(side), Some(price), Some(size)) = 
+                        let order_type = action.order_type.unwrap_or_default();
+                        if let (Some(side), Some(price), Some(size)) =
                             (action.side, action.price, action.size) {
-                            book.add_order(
+                            let fills = book.add_order(
                                 action.order_id,
                                 side == "buy",
                                 price,
                                 size,
                                 action.timestamp.unwrap_or(0),
+                                order_type,
                             );
+                            for fill in fills {
+                                info!(market_id = book.market_id, ?fill, "order filled");
+                            }
                         }
                     }
                     "cancel" => {
                         book.cancel_order(action.order_id);
                     }
                     "modify" => {
-                        if let (Some(side), Some(price), Some(size)) = 
+                        let order_type = action.order_type.unwrap_or_default();
+                        if let (Some(side), Some(price), Some(size)) =
                             (action.side, action.price, action.size) {
                             book.cancel_order(action.order_id);
-                            book.add_order(
+                            let fills = book.add_order(
                                 action.order_id,
                                 side == "buy",
                                 price,
                                 size,
                                 action.timestamp.unwrap_or(0),
+                                order_type,
                             );
+                            for fill in fills {
+                                info!(market_id = book.market_id, ?fill, "order filled");
+                            }
                         }
                     }
                     _ => {}
@@ -275,8 +939,13 @@ async fn main() {
     info!("Starting Orderbook Engine (Rust)");
 
     // Initialize engine
-    let market_ids = vec![0, 1, 159, 107]; // BTC, ETH, HYPE, ALT
-    let (engine, rx) = OrderbookEngine::new(market_ids);
+    let markets = vec![
+        (0, MarketConfig { tick_size: 0.01, lot_size: 0.0001 }),    // BTC
+        (1, MarketConfig { tick_size: 0.01, lot_size: 0.001 }),     // ETH
+        (159, MarketConfig { tick_size: 0.001, lot_size: 0.1 }),    // HYPE
+        (107, MarketConfig { tick_size: 0.0001, lot_size: 1.0 }),   // ALT
+    ];
+    let (engine, rx) = OrderbookEngine::new(markets);
     let engine = Arc::new(engine);
 
     // Start order processor
@@ -293,6 +962,9 @@ async fn main() {
         side: Some("buy".to_string()),
         price: Some(34.50),
         size: Some(100.0),
+        order_type: None,
+        first_update_id: None,
+        final_update_id: None,
         timestamp: Some(1234567890),
     }).await;
 
@@ -305,4 +977,164 @@ async fn main() {
     // Keep running
     tokio::signal::ctrl_c().await.unwrap();
     info!("Shutting down");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> MarketConfig {
+        MarketConfig { tick_size: 0.01, lot_size: 0.01 }
+    }
+
+    #[test]
+    fn crossing_limit_order_fills_and_rests_remainder() {
+        let book = Orderbook::new(0, test_config());
+        book.add_order(1, false, 100.00, 10.0, 1, OrderType::Limit);
+
+        let fills = book.add_order(2, true, 100.00, 15.0, 2, OrderType::Limit);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].maker_order_id, 1);
+        assert_eq!(fills[0].taker_order_id, 2);
+        assert_eq!(fills[0].price, 100.00);
+        assert_eq!(fills[0].size, 10.0);
+
+        assert!(book.best_ask().is_none());
+        let (bid_price, bid_size) = book.best_bid().expect("remainder should rest");
+        assert_eq!(bid_price, 100.00);
+        assert_eq!(bid_size, 5.0);
+    }
+
+    #[test]
+    fn immediate_or_cancel_drops_unfilled_remainder() {
+        let book = Orderbook::new(0, test_config());
+        book.add_order(1, false, 100.00, 5.0, 1, OrderType::Limit);
+
+        let fills = book.add_order(2, true, 100.00, 10.0, 2, OrderType::ImmediateOrCancel);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].size, 5.0);
+        assert!(book.best_ask().is_none());
+        assert!(book.best_bid().is_none(), "IOC remainder must be dropped, not rested");
+    }
+
+    #[test]
+    fn post_only_rejects_at_crossing_price() {
+        let book = Orderbook::new(0, test_config());
+        book.add_order(1, false, 100.00, 5.0, 1, OrderType::Limit);
+
+        let fills = book.add_order(2, true, 100.00, 5.0, 2, OrderType::PostOnly);
+
+        assert!(fills.is_empty());
+        assert!(book.best_bid().is_none(), "post-only must reject, not rest, at a crossing price");
+        let (ask_price, ask_size) = book.best_ask().expect("resting ask untouched");
+        assert_eq!(ask_price, 100.00);
+        assert_eq!(ask_size, 5.0);
+    }
+
+    #[test]
+    fn resting_orders_at_same_level_sum_correctly() {
+        // Regression test for the old `total_size.fetch_add(order.size.to_bits())`
+        // bug: summing two f64 bit patterns as if they were integers produced
+        // garbage rather than the true aggregate size. With three resting
+        // orders at one price, the level's reported size must equal the sum
+        // of the individual order sizes.
+        let book = Orderbook::new(0, test_config());
+        book.add_order(1, false, 100.00, 1.5, 1, OrderType::Limit);
+        book.add_order(2, false, 100.00, 2.25, 2, OrderType::Limit);
+        book.add_order(3, false, 100.00, 0.75, 3, OrderType::Limit);
+
+        let (ask_price, ask_size) = book.best_ask().expect("resting asks");
+        assert_eq!(ask_price, 100.00);
+        assert_eq!(ask_size, 4.5);
+
+        let snapshot = book.get_snapshot(10);
+        assert_eq!(snapshot.asks.len(), 1);
+        assert_eq!(snapshot.asks[0].size, 4.5);
+        assert_eq!(snapshot.asks[0].orders, 3);
+    }
+
+    #[test]
+    fn subscribe_then_add_order_delivers_level_update_with_sequence() {
+        let book = Orderbook::new(0, test_config());
+        let (checkpoint, mut rx) = book.subscribe();
+        assert_eq!(checkpoint.sequence, 0);
+        assert!(checkpoint.bids.is_empty());
+        assert!(checkpoint.asks.is_empty());
+
+        book.add_order(1, true, 100.00, 5.0, 1, OrderType::Limit);
+
+        match rx.try_recv().expect("a level update should have been published") {
+            BookEvent::Level(update) => {
+                assert!(update.is_bid);
+                assert_eq!(update.price, 100.00);
+                assert_eq!(update.size, 5.0);
+                assert_eq!(update.order_count, 1);
+                assert_eq!(update.sequence, 1);
+            }
+            BookEvent::Fill(_) => panic!("expected a level update, not a fill"),
+        }
+    }
+
+    fn sync_level_action(first_update_id: u64, final_update_id: u64, price: f64, size: f64) -> OrderAction {
+        OrderAction {
+            action: "sync_level".to_string(),
+            asset: 0,
+            order_id: 0,
+            side: Some("buy".to_string()),
+            price: Some(price),
+            size: Some(size),
+            timestamp: Some(0),
+            order_type: None,
+            first_update_id: Some(first_update_id),
+            final_update_id: Some(final_update_id),
+        }
+    }
+
+    #[test]
+    fn depth_cache_sync_flips_unsynced_on_live_sequence_gap() {
+        let book = Arc::new(Orderbook::new(0, test_config()));
+        let sync = DepthCacheSync::new(book);
+        sync.apply_snapshot(100, vec![], vec![]);
+        assert!(sync.is_synced());
+
+        let err = sync.ingest(sync_level_action(105, 106, 10.0, 1.0));
+
+        assert!(matches!(err, Err(DepthSyncError::SequenceGap { expected: 101, got: 105 })));
+        assert!(!sync.is_synced());
+    }
+
+    #[test]
+    fn depth_cache_sync_flips_unsynced_on_buffered_gap_during_apply_snapshot() {
+        let book = Arc::new(Orderbook::new(0, test_config()));
+        let sync = DepthCacheSync::new(book);
+
+        // Arrives before any snapshot, so both get buffered rather than applied.
+        sync.ingest(sync_level_action(5, 10, 10.0, 1.0)).unwrap();
+        sync.ingest(sync_level_action(15, 20, 10.0, 1.0)).unwrap(); // gap: expects 11
+
+        sync.apply_snapshot(4, vec![], vec![]);
+
+        assert!(!sync.is_synced(), "a gap discovered while draining the buffer must not report synced");
+        assert_eq!(sync.last_update_id.load(Ordering::Relaxed), 10, "only the contiguous event should have applied");
+    }
+
+    #[test]
+    fn read_accessors_reflect_resting_orders() {
+        let book = Orderbook::new(0, test_config());
+        book.add_order(1, true, 100.00, 5.0, 1, OrderType::Limit);
+        book.add_order(2, false, 101.00, 3.0, 2, OrderType::Limit);
+
+        assert_eq!(book.mid_price(), Some(100.50));
+        assert_eq!(book.spread(), Some(1.00));
+
+        let depth = book.get_orderbook_with_depth(10);
+        assert_eq!(depth.bids, vec![[100.00, 5.0]]);
+        assert_eq!(depth.asks, vec![[101.00, 3.0]]);
+
+        let ticker = book.ticker();
+        assert_eq!(ticker.best_bid, Some((100.00, 5.0)));
+        assert_eq!(ticker.best_ask, Some((101.00, 3.0)));
+    }
 }
\ No newline at end of file